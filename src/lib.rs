@@ -30,9 +30,164 @@
 //! Use `pub` before the`enum` keyword to export it.
 //! Attributes can be attached to both the `enum` and the structures generated.
 //! The `Copy`, `Clone`, `Debug`, `Eq`, `PartialEq`, `Ord`, `PartialOrd`, and `Hash` traits are
-//! automatically derived for the types using the derive attribute. At the moment, the macro
-//! can only be used once per module, so if you need to define multiple structures you should
-//! put them in separate submodules.
+//! automatically derived for the types using the derive attribute. The lookup trait is named
+//! after the enum (`Lang` generates a `LangWord` trait), so several independent tables can live
+//! side by side in the same module:
+//!
+//! ```
+//! # use reword::reword;
+//! reword! {
+//!     enum Lang: &'static str {
+//!         struct Hi {
+//!             const NO = "Hei";
+//!             const EN = "Hi";
+//!         }
+//!     }
+//! }
+//!
+//! reword! {
+//!     enum Casing: &'static str {
+//!         struct Yes {
+//!             const LOWER = "yes";
+//!             const UPPER = "YES";
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::NO.get::<Hi>(), "Hei");
+//! assert_eq!(Casing::UPPER.get::<Yes>(), "YES");
+//! ```
+//!
+//! The default derive set can be replaced with a leading `#[reword(derive(...))]` directive.
+//! The listed traits are then derived verbatim on both the enum and the key structs, which is
+//! useful for dropping traits that are not meaningful or attaching extra ones such as
+//! `serde::Serialize`:
+//!
+//! ```
+//! # use reword::reword;
+//! reword! {
+//!     #[reword(derive(Copy, Clone, Debug, Eq, PartialEq))]
+//!     enum Lang: &'static str {
+//!         struct Hi {
+//!             const NO = "Hei";
+//!             const EN = "Hi";
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::EN.get::<Hi>(), "Hi");
+//! ```
+//!
+//! The active variant can be selected at runtime by parsing its identifier with [`FromStr`]
+//! or [`TryFrom<&str>`](core::convert::TryFrom), which is handy when the value comes from an
+//! environment variable, an `Accept-Language` header, or a config file:
+//!
+//! ```
+//! # use reword::reword;
+//! use core::str::FromStr;
+//!
+//! reword! {
+//!     enum Lang: &'static str {
+//!         struct Hi {
+//!             const NO = "Hei";
+//!             const EN_UK | EN_US = "Hi";
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::from_str("EN_UK"), Ok(Lang::EN_UK));
+//! assert!(Lang::from_str("FR").is_err());
+//! ```
+//!
+//! [`FromStr`]: core::str::FromStr
+//!
+//! Every generated variant is also available through the `ALL` slice and the `variants`
+//! iterator, for building pickers, validating config, or dumping every translation:
+//!
+//! ```
+//! # use reword::reword;
+//! reword! {
+//!     enum Lang: &'static str {
+//!         struct Hi {
+//!             const NO = "Hei";
+//!             const EN = "Hi";
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::ALL, &[Lang::NO, Lang::EN]);
+//! assert_eq!(Lang::variants().count(), 2);
+//! ```
+//!
+//! A key struct may end with a `const _ = ...;` line, a default that fills in every variant
+//! not assigned on an earlier line. This keeps tables with many variants readable when most of
+//! them share a value. Explicit assignments always take precedence over the default, so a
+//! variant listed on its own line keeps that value and is never filled in. The first struct
+//! must still enumerate every variant, as it defines the set the defaults are expanded against:
+//!
+//! ```
+//! # use reword::reword;
+//! reword! {
+//!     enum Lang: &'static str {
+//!         struct Hi {
+//!             const NO = "Hei";
+//!             const EN_UK | EN_US = "Hi";
+//!         }
+//!         struct Humor {
+//!             const EN_UK = "Humour";
+//!             const _ = "Humor";
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::EN_UK.get::<Humor>(), "Humour");
+//! assert_eq!(Lang::NO.get::<Humor>(), "Humor");
+//! assert_eq!(Lang::EN_US.get::<Humor>(), "Humor");
+//! ```
+//!
+//! Use `get_ref` instead of `get` to borrow the value from the generated constant rather than
+//! copy it, which matters for large or non-`Copy` value types such as `[u8; N]`:
+//!
+//! ```
+//! # use reword::reword;
+//! reword! {
+//!     enum Lang: [u8; 2] {
+//!         struct Flag {
+//!             const NO = [0, 1];
+//!             const EN = [2, 3];
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::NO.get_ref::<Flag>(), &[0, 1]);
+//! ```
+//!
+//! When both the language and the key are runtime values, look the value up with `get_dyn` and
+//! the generated key enum, named after the enum like the lookup trait (`Lang` generates
+//! `LangKey`, with one variant per key struct). This drives lookups from data without trait
+//! objects or allocation.
+//! `get_dyn_ref` is the borrowing counterpart, mirroring `get_ref` for large or non-`Copy`
+//! value types:
+//!
+//! ```
+//! # use reword::reword;
+//! reword! {
+//!     enum Lang: &'static str {
+//!         struct Hi {
+//!             const NO = "Hei";
+//!             const EN = "Hi";
+//!         }
+//!         struct Humor {
+//!             const NO = "Humor";
+//!             const EN = "Humour";
+//!         }
+//!     }
+//! }
+//!
+//! assert_eq!(Lang::EN.get_dyn(LangKey::Hi), "Hi");
+//! assert_eq!(Lang::EN.get_dyn(LangKey::Humor), "Humour");
+//! assert_eq!(Lang::NO.get_dyn_ref(LangKey::Humor), &"Humor");
+//! ```
 
 #![no_std]
 #![doc(html_root_url = "https://docs.rs/reword/3.0.1")]
@@ -52,55 +207,255 @@
 /// See the [crate level docs](index.html) for more information.
 #[macro_export]
 macro_rules! reword {
+    // Caller-supplied derive set: used verbatim on both the enum and the key structs.
+    (
+        #[reword(derive($($derive:path),+ $(,)?))]
+        $($rest:tt)*
+    ) => {
+        $crate::reword! {
+            @build
+            [derive($($derive),+)]
+            [derive($($derive),+)]
+            $($rest)*
+        }
+    };
+    // Reject a `const _` default in the first key struct with a clear message: the first
+    // struct defines the variant universe the defaults are expanded against, so it must
+    // enumerate every variant itself.
+    (
+        @build
+        [$enum_derive:meta]
+        [$key_derive:meta]
+        $(#[$enum_meta:meta])*
+        $pub:vis enum $enum:ident : $T:ty {
+            $(#[$key_meta:meta])*
+            struct $key:ident {
+                $($(#[$name_meta:meta])* const $($name:ident)|+ = $val:expr;)*
+                const _ = $default:expr;
+                $($rest:tt)*
+            }
+            $($more:tt)*
+        }
+    ) => {
+        compile_error!(concat!(
+            "`const _` defaults are not allowed in the first key struct `",
+            stringify!($key),
+            "`; the first struct must enumerate every variant"
+        ));
+    };
     (
+        @build
+        [$enum_derive:meta]
+        [$key_derive:meta]
         $(#[$enum_meta:meta])*
         $pub:vis enum $enum:ident : $T:ty {
             $(#[$key_meta:meta])*
             struct $key:ident { $($(#[$name_meta:meta])* const $($name:ident)|+ = $val:expr;)+ }
             $(
                 $(#[$key2_meta:meta])*
-                struct $key2:ident { $($(#[$name2_meta:meta])* const $($name2:ident)|+ = $val2:expr;)+ }
+                struct $key2:ident { $($key2_body:tt)* }
             )*
         }
     ) => {
-        #[doc = "Trait used for constant value lookup."]
-        #[doc(hidden)]
-        $pub trait Word {
-            $($(#[$name_meta])* $(#[allow(non_upper_case_globals)] const $name: $T;)+)+
-        }
+        $crate::__paste::paste! {
+            #[doc = "Trait used for constant value lookup."]
+            #[doc(hidden)]
+            $pub trait [<$enum Word>] {
+                $($(#[$name_meta])* $(#[allow(non_upper_case_globals)] const $name: $T;)+)+
+            }
 
-        $(#[$enum_meta])*
-        #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
-        $pub enum $enum {
-            $($(#[allow(non_camel_case_types)] $name,)+)+
-        }
+            $(#[$enum_meta])*
+            #[$enum_derive]
+            $pub enum $enum {
+                $($(#[allow(non_camel_case_types)] $name,)+)+
+            }
+
+            #[doc = "Key used for dynamic value lookup, one variant per generated key struct."]
+            #[$enum_derive]
+            $pub enum [<$enum Key>] {
+                $key,
+                $($key2,)*
+            }
+
+            impl $enum {
+                #[doc = "Returns the value of `W`."]
+                #[inline]
+                $pub fn get<W: [<$enum Word>] + ?Sized>(self) -> $T {
+                    match self {
+                        $($($enum::$name => W::$name,)+)+
+                    }
+                }
+
+                #[doc = "Returns the value of `key`, chosen at runtime."]
+                #[inline]
+                $pub fn get_dyn(self, key: [<$enum Key>]) -> $T {
+                    match key {
+                        [<$enum Key>]::$key => self.get::<$key>(),
+                        $([<$enum Key>]::$key2 => self.get::<$key2>(),)*
+                    }
+                }
 
-        impl $enum {
-            #[doc = "Returns the value of `W`."]
-            #[inline]
-            $pub fn get<W: Word + ?Sized>(self) -> $T {
-                match self {
-                    $($($enum::$name => W::$name,)+)+
+                #[doc = "Returns a reference to the value of `key`, chosen at runtime."]
+                #[inline]
+                $pub fn get_dyn_ref(self, key: [<$enum Key>]) -> &'static $T {
+                    match key {
+                        [<$enum Key>]::$key => self.get_ref::<$key>(),
+                        $([<$enum Key>]::$key2 => self.get_ref::<$key2>(),)*
+                    }
+                }
+
+                #[doc = "Returns a reference to the value of `W`."]
+                #[doc = ""]
+                #[doc = "Useful when the value type is large or not `Copy`, as the value is"]
+                #[doc = "borrowed from the generated constant instead of returned by value."]
+                #[inline]
+                $pub fn get_ref<W: [<$enum Word>] + ?Sized>(self) -> &'static $T {
+                    match self {
+                        $($($enum::$name => &W::$name,)+)+
+                    }
+                }
+
+                #[doc = "Every variant, in declaration order."]
+                $pub const ALL: &'static [$enum] = &[$($($enum::$name,)+)+];
+
+                #[doc = "Returns an iterator over every variant, in declaration order."]
+                #[inline]
+                $pub fn variants() -> impl core::iter::Iterator<Item = $enum> {
+                    Self::ALL.iter().copied()
                 }
             }
-        }
 
-        $(#[$key_meta])*
-        #[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
-        $pub struct $key;
+            #[doc = "The error returned when a string does not name a variant."]
+            #[derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+            $pub struct [<Parse $enum Error>];
 
-        impl Word for $key {
-            $($(#[$name_meta])* $(#[allow(non_upper_case_globals)] const $name: $T = $val;)+)+
+            impl core::fmt::Display for [<Parse $enum Error>] {
+                #[inline]
+                fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                    f.write_str(concat!("not a valid `", stringify!($enum), "`"))
+                }
+            }
+
+            impl core::str::FromStr for $enum {
+                type Err = [<Parse $enum Error>];
+
+                #[inline]
+                fn from_str(s: &str) -> core::result::Result<Self, Self::Err> {
+                    match s {
+                        $($(stringify!($name) => core::result::Result::Ok($enum::$name),)+)+
+                        _ => core::result::Result::Err([<Parse $enum Error>]),
+                    }
+                }
+            }
+
+            impl<'a> core::convert::TryFrom<&'a str> for $enum {
+                type Error = [<Parse $enum Error>];
+
+                #[inline]
+                fn try_from(s: &'a str) -> core::result::Result<Self, Self::Error> {
+                    core::str::FromStr::from_str(s)
+                }
+            }
+
+            $(#[$key_meta])*
+            #[$key_derive]
+            $pub struct $key;
+
+            impl [<$enum Word>] for $key {
+                $($(#[$name_meta])* $(#[allow(non_upper_case_globals)] const $name: $T = $val;)+)+
+            }
         }
 
+        $crate::reword! {
+            @keys ($) [$key_derive] $pub [$enum] [$T] [$($($name)+)+]
+            $(
+                $(#[$key2_meta])*
+                struct $key2 { $($key2_body)* }
+            )*
+        }
+    };
+    // Emit each of the remaining key structs. The variant universe is carried as a single
+    // token tree so it stays constant across the per-struct repetition.
+    (
+        @keys ($d:tt) [$key_derive:meta] $pub:vis [$enum:ident] [$T:ty] $all:tt
         $(
-            $(#[$key2_meta])*
-            #[derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
-            $pub struct $key2;
-
-            impl Word for $key2 {
-                $($(#[$name2_meta])* $(#[allow(non_upper_case_globals)] const $name2: $T = $val2;)+)+
+            $(#[$key_meta:meta])*
+            struct $key:ident { $($key_body:tt)* }
+        )*
+    ) => {
+        $(
+            $crate::reword! {
+                @key ($d) [$key_derive] $pub [$enum] [$T] all $all
+                $(#[$key_meta])*
+                struct $key { $($key_body)* }
             }
         )*
     };
+    // A key struct whose body ends with a `const _ = ...;` default line: every variant in
+    // `all` that is not explicitly assigned is filled in with the default value. The default
+    // must be the last line so the explicit assignments can be matched as regular `const`s.
+    (
+        @key ($d:tt) [$key_derive:meta] $pub:vis [$enum:ident] [$T:ty] all [$($all:ident)*]
+        $(#[$key_meta:meta])*
+        struct $key:ident {
+            $($(#[$name_meta:meta])* const $($name:ident)|+ = $val:expr;)*
+            const _ = $default:expr;
+        }
+    ) => {
+        $crate::__paste::paste! {
+            macro_rules! __reword_fill {
+                $($(($name) => {};)+)*
+                ($d name:ident) => {
+                    #[allow(non_upper_case_globals)] const $d name: $T = $default;
+                };
+            }
+
+            $(#[$key_meta])*
+            #[$key_derive]
+            $pub struct $key;
+
+            impl [<$enum Word>] for $key {
+                $($(#[$name_meta])* $(#[allow(non_upper_case_globals)] const $name: $T = $val;)+)*
+                $(__reword_fill! { $all })*
+            }
+        }
+    };
+    // A key struct that enumerates every variant explicitly (the original form).
+    (
+        @key ($d:tt) [$key_derive:meta] $pub:vis [$enum:ident] [$T:ty] all [$($all:ident)*]
+        $(#[$key_meta:meta])*
+        struct $key:ident {
+            $($(#[$name_meta:meta])* const $($name:ident)|+ = $val:expr;)+
+        }
+    ) => {
+        $crate::__paste::paste! {
+            $(#[$key_meta])*
+            #[$key_derive]
+            $pub struct $key;
+
+            impl [<$enum Word>] for $key {
+                $($(#[$name_meta])* $(#[allow(non_upper_case_globals)] const $name: $T = $val;)+)+
+            }
+        }
+    };
+    // No directive: fall back to the default derives. The enum cannot derive `Default`,
+    // so the key structs get a slightly wider set than the enum. This arm matches the real
+    // `enum` structure rather than a bare `$($rest:tt)*` so that a malformed invocation (or an
+    // `@build …` call that failed to match above) fails with a direct error instead of looping
+    // back in here and blowing the recursion limit.
+    (
+        $(#[$enum_meta:meta])*
+        $pub:vis enum $enum:ident : $T:ty { $($body:tt)* }
+    ) => {
+        $crate::reword! {
+            @build
+            [derive(Copy, Clone, Debug, Hash, Eq, PartialEq, Ord, PartialOrd)]
+            [derive(Copy, Clone, Debug, Default, Hash, Ord, PartialOrd, Eq, PartialEq)]
+            $(#[$enum_meta])*
+            $pub enum $enum : $T { $($body)* }
+        }
+    };
 }
+
+#[doc(hidden)]
+pub use paste as __paste;